@@ -1,30 +1,135 @@
 // Copyright 2025 Oleg Pavlenko
 
-use std::{cell::Cell, error::Error, marker::PhantomData, time::{Duration, Instant}};
+use std::{any::Any, cell::Cell, collections::VecDeque, error::Error, marker::PhantomData, panic::{self, AssertUnwindSafe}, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc, Condvar, Mutex}, time::Duration};
 type FnThread = Box<dyn FnOnce() + 'static + Send>;
+type PanicHandler = Box<dyn Fn(&(dyn Any + Send)) + 'static + Send + Sync>;
+type SharedQueue = Arc<(Mutex<VecDeque<Message>>, Condvar)>;
+// Worker handles, shared so a `Sentinel` can write a respawned worker's new
+// handle back into the same slot instead of it going untracked.
+type SharedThreads = Arc<Mutex<Vec<Option<std::thread::JoinHandle<()>>>>>;
+
+// What gets sent down a worker's channel: either a job to run, or the
+// signal to stop taking new jobs and let the thread end.
+enum Message {
+    Job(FnThread),
+    Terminate
+}
+
+///
+/// How jobs submitted via `spawn` are handed to workers.
+///
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum Scheduling {
+    /// Every worker has its own queue and `Balancer` picks one at submit time (the default).
+    #[default]
+    RoundRobin,
+    /// All workers pull from one shared queue, so an idle worker never waits behind a busy one.
+    WorkStealing
+}
+
+// Where a worker pulls its next `Message` from.
+enum WorkerSource {
+    Channel(std::sync::mpsc::Receiver<Message>),
+    Shared(SharedQueue)
+}
+
+impl WorkerSource {
+    fn recv(&self) -> Result<Message, ()> {
+        match self {
+            WorkerSource::Channel(rx) => rx.recv().map_err(|_| ()),
+            WorkerSource::Shared(shared) => {
+                let (queue, ready) = &**shared;
+                let mut queue = queue.lock().unwrap();
+                loop {
+                    if let Some(message) = queue.pop_front() { return Ok(message); }
+                    queue = ready.wait(queue).unwrap();
+                }
+            }
+        }
+    }
+}
+
+// How submitted jobs reach the workers, mirroring the chosen `Scheduling`.
+enum Dispatch {
+    RoundRobin { senders: Vec<std::sync::mpsc::Sender<Message>> },
+    WorkStealing { queue: SharedQueue }
+}
+
+// Shared runtime counters, updated from inside the worker loop so they stay
+// accurate regardless of which `Dispatch` is in use. Also backs the optional
+// bounded queue: `capacity` caps how many jobs may be queued at once, and
+// `room` is what `spawn` parks on while waiting for a worker to free a slot.
+struct Counters {
+    queued: AtomicUsize,
+    active: AtomicUsize,
+    panics: AtomicUsize,
+    capacity: Option<usize>,
+    room: (Mutex<()>, Condvar),
+    panic_handler: Option<PanicHandler>
+}
+
+impl Counters {
+    fn new(capacity: Option<usize>, panic_handler: Option<PanicHandler>) -> Self {
+        Self {
+            queued: AtomicUsize::new(0),
+            active: AtomicUsize::new(0),
+            panics: AtomicUsize::new(0),
+            capacity,
+            room: (Mutex::new(()), Condvar::new()),
+            panic_handler
+        }
+    }
+
+    fn has_room(&self) -> bool {
+        match self.capacity {
+            Some(capacity) => self.queued.load(Ordering::Relaxed) < capacity,
+            None => true
+        }
+    }
+
+    fn wait_for_room(&self) {
+        if self.capacity.is_none() { return; }
+
+        let (lock, ready) = &self.room;
+        let mut guard = lock.lock().unwrap();
+        while !self.has_room() {
+            guard = ready.wait(guard).unwrap();
+        }
+    }
+
+    fn notify_room(&self) {
+        let (lock, ready) = &self.room;
+        let _guard = lock.lock().unwrap();
+        ready.notify_one();
+    }
+}
 
 ///
 /// Builder for MiniPool
 /// 
 /// ```
+/// # use minipool::MiniPool;
 /// fn main() {
 ///     let pool = MiniPool::builder()
 ///                             .count_threads(4)
-///                             .build;
-/// 
+///                             .build();
+///
 ///     // ...
 /// }
 /// ```
-/// 
+///
 #[derive(Default)]
 pub struct MiniPoolBuilder {
         #[allow(dead_code)] // clippy never read fix
-        senders: Option<Vec<std::sync::mpsc::Sender<FnThread>>>,
+        senders: Option<Vec<std::sync::mpsc::Sender<Message>>>,
         #[allow(dead_code)] // clippy never read fix
-        threads: Option<Vec<std::thread::JoinHandle<()>>>,
+        threads: Option<Vec<Option<std::thread::JoinHandle<()>>>>,
         balance: Option<Box<dyn Balancer>>,
         count_threads: Option<usize>,
-        stack_size: Option<usize>
+        stack_size: Option<usize>,
+        panic_handler: Option<PanicHandler>,
+        scheduling: Option<Scheduling>,
+        queue_capacity: Option<usize>
 }
 
 impl MiniPoolBuilder {
@@ -47,34 +152,144 @@ impl MiniPoolBuilder {
         self
     }
 
-    pub fn build(self) -> Result<MiniPool, Box<dyn Error>> {
+    ///
+    /// Registers a handler invoked whenever a task running on this pool's
+    /// workers panics, with the payload `catch_unwind` caught. Without one,
+    /// panics are still caught so the worker survives and counted in
+    /// `panic_count`; the process-wide panic hook still runs for them as
+    /// usual, and nothing else reports them. Unlike a global `std::panic::set_hook`, this
+    /// handler only ever sees panics from this pool's own tasks.
+    ///
+    pub fn panic_handler(mut self, handler: impl Fn(&(dyn Any + Send)) + 'static + Send + Sync) -> Self {
+        self.panic_handler = Some(Box::new(handler));
+        self
+    }
 
-        let count = self.count_threads.unwrap_or(std::thread::available_parallelism().expect("Not found count of threads").into());
-        let mut threads = Vec::with_capacity(count.into());
-        let mut senders = Vec::with_capacity(count.into());
+    ///
+    /// Chooses how submitted jobs are handed to workers. Defaults to `Scheduling::RoundRobin`.
+    ///
+    pub fn scheduling(mut self, scheduling: Scheduling) -> Self {
+        self.scheduling = Some(scheduling);
+        self
+    }
+
+    ///
+    /// Caps how many submitted jobs may sit queued at once. Once reached,
+    /// `spawn` blocks for room and `try_spawn` rejects instead of growing
+    /// the queue without bound.
+    ///
+    pub fn queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = Some(capacity);
+        self
+    }
 
-        for _ in 0..count.into() {
+    pub fn build(self) -> Result<MiniPool, Box<dyn Error>> {
 
-            let (sx, rx) = std::sync::mpsc::channel::<FnThread>();
-            senders.push(sx);
+        let count: usize = self.count_threads.unwrap_or(std::thread::available_parallelism().expect("Not found count of threads").into());
+        let threads: SharedThreads = Arc::new(Mutex::new(Vec::with_capacity(count)));
+        let counters = Arc::new(Counters::new(self.queue_capacity, self.panic_handler));
 
-            let builder = if let Some(size) = self.stack_size {
-                std::thread::Builder::new()
-                    .stack_size(size)
-            } else {
-                std::thread::Builder::new()
+        let spawn_into = |source: WorkerSource| {
+            let index = {
+                let mut threads = threads.lock().unwrap();
+                threads.push(None);
+                threads.len() - 1
             };
 
-            threads.push(
-                builder.spawn(move || {
-                    while let Ok(func) = rx.recv() {
-                        func();
-                    }
-                }).unwrap()
-            );
+            let handle = spawn_worker(source, self.stack_size, counters.clone(), threads.clone(), index);
+            threads.lock().unwrap()[index] = Some(handle);
+        };
+
+        let dispatch = match self.scheduling.unwrap_or_default() {
+            Scheduling::RoundRobin => {
+                let mut senders = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let (sx, rx) = std::sync::mpsc::channel::<Message>();
+                    senders.push(sx);
+
+                    spawn_into(WorkerSource::Channel(rx));
+                }
+
+                Dispatch::RoundRobin { senders }
+            }
+            Scheduling::WorkStealing => {
+                let queue: SharedQueue = Arc::new((Mutex::new(VecDeque::new()), Condvar::new()));
+
+                for _ in 0..count {
+                    spawn_into(WorkerSource::Shared(queue.clone()));
+                }
+
+                Dispatch::WorkStealing { queue }
+            }
+        };
+
+        Ok(MiniPool { phantom: PhantomData, dispatch, threads, counters, balance: self.balance.unwrap_or(Box::new(DefaultBalancer{ index: Cell::new(0) })) })
+    }
+}
+
+///
+/// Runs a worker thread over `source`. The loop catches panics from individual
+/// jobs so one bad task can't take the thread down; if the thread does die
+/// anyway (e.g. a poisoned lock), the `Sentinel` it carries notices on drop
+/// and spawns a replacement bound to the same source, keeping the
+/// configured `count_threads` alive for the lifetime of the pool.
+///
+fn spawn_worker(source: WorkerSource, stack_size: Option<usize>, counters: Arc<Counters>, threads: SharedThreads, index: usize) -> std::thread::JoinHandle<()> {
+
+    let builder = if let Some(size) = stack_size {
+        std::thread::Builder::new().stack_size(size)
+    } else {
+        std::thread::Builder::new()
+    };
+
+    builder.spawn(move || {
+        let mut sentinel = Sentinel { source: Some(source), stack_size, counters: counters.clone(), threads, index, active: true };
+
+        while let Ok(Message::Job(func)) = sentinel.source.as_ref().unwrap().recv() {
+            counters.queued.fetch_sub(1, Ordering::Relaxed);
+            counters.notify_room();
+            counters.active.fetch_add(1, Ordering::Relaxed);
+
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(func)) {
+                counters.panics.fetch_add(1, Ordering::Relaxed);
+                if let Some(handler) = &counters.panic_handler {
+                    handler(payload.as_ref());
+                }
+            }
+
+            counters.active.fetch_sub(1, Ordering::Relaxed);
         }
 
-        Ok(MiniPool { phantom: PhantomData, senders, threads, balance: self.balance.unwrap_or(Box::new(DefaultBalancer{ index: Cell::new(0) })) })
+        sentinel.active = false;
+    }).unwrap()
+}
+
+///
+/// Respawns the worker it belongs to if dropped while still `active`, i.e.
+/// the thread unwound out of its receive loop instead of leaving it on a
+/// `Terminate` message or a closed channel. Writes
+/// the replacement's handle back into `threads` at the same `index` so
+/// `MiniPool::join_all` still waits for it.
+///
+struct Sentinel {
+    source: Option<WorkerSource>,
+    stack_size: Option<usize>,
+    counters: Arc<Counters>,
+    threads: SharedThreads,
+    index: usize,
+    active: bool
+}
+
+impl Drop for Sentinel {
+    #[allow(clippy::collapsible_if)] // keeps the active check and edition-2021-safe nested `if let` separate
+    fn drop(&mut self) {
+        if self.active {
+            if let Some(source) = self.source.take() {
+                let handle = spawn_worker(source, self.stack_size, self.counters.clone(), self.threads.clone(), self.index);
+                self.threads.lock().unwrap()[self.index] = Some(handle);
+            }
+        }
     }
 }
 
@@ -84,7 +299,7 @@ struct DefaultBalancer {
 
 impl Balancer for DefaultBalancer {
     fn index(&self, state: &MiniPool) -> usize {
-        if self.index.clone().into_inner() == state.threads.len() { self.index.set(0); }
+        if self.index.clone().into_inner() == state.threads.lock().unwrap().len() { self.index.set(0); }
         let idx = self.index.clone().into_inner();
         self.index.set(idx + 1);
         idx
@@ -95,21 +310,128 @@ pub trait Balancer {
     fn index(&self, state: &MiniPool) -> usize;
 }
 
+///
+/// Handle to the result of a task submitted with `spawn_with_result`.
+///
+/// The result is delivered over a per-call channel, so the handle can be
+/// handed off anywhere and joined exactly once.
+///
+pub struct TaskHandle<T> {
+    receiver: std::sync::mpsc::Receiver<T>
+}
+
+impl<T> TaskHandle<T> {
+
+    ///
+    /// Blocks until the task finishes and returns its result.
+    ///
+    pub fn join(self) -> Result<T, std::sync::mpsc::RecvError> {
+        self.receiver.recv()
+    }
+
+    ///
+    /// Returns the result without blocking if the task has already finished.
+    ///
+    pub fn try_recv(&self) -> Result<T, std::sync::mpsc::TryRecvError> {
+        self.receiver.try_recv()
+    }
+}
+
+///
+/// A cheaply cloneable flag a task can poll to learn it should stop early.
+/// Cancellation is cooperative: nothing forces the closure to check it.
+///
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+
+    pub fn new() -> Self {
+        Self(Arc::new(AtomicBool::new(false)))
+    }
+
+    /// Requests cancellation. Has no effect on a task that doesn't check `is_cancelled`.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Returned by `TimeoutHandle::join` when the deadline fired before the task finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut;
+
+///
+/// Returned by `try_spawn` when the queue is at capacity. Carries the
+/// rejected closure back so the caller can retry, drop it, or run it inline.
+///
+pub struct SpawnError<F>(pub F);
+
+impl<F> std::fmt::Debug for SpawnError<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SpawnError").field(&"<closure>").finish()
+    }
+}
+
+impl<F> std::fmt::Display for SpawnError<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "queue is at capacity")
+    }
+}
+
+impl<F> Error for SpawnError<F> {}
+
+struct TimeoutShared<T> {
+    slot: Mutex<Option<T>>,
+    ready: Condvar
+}
+
+///
+/// Handle to a task submitted with `spawn_with_timeout`.
+///
+pub struct TimeoutHandle<T> {
+    shared: Arc<TimeoutShared<T>>,
+    timeout: Duration
+}
+
+impl<T> TimeoutHandle<T> {
+
+    ///
+    /// Blocks up to the configured timeout for the task to finish. Parks on
+    /// a `Condvar` rather than polling, so waiting costs nothing until the
+    /// task completes or the deadline fires.
+    ///
+    pub fn join(self) -> Result<T, TimedOut> {
+        let guard = self.shared.slot.lock().unwrap();
+        let (mut guard, _) = self.shared.ready
+            .wait_timeout_while(guard, self.timeout, |slot| slot.is_none())
+            .unwrap();
+
+        guard.take().ok_or(TimedOut)
+    }
+}
+
 ///
 /// Create pool threads for execute parallel cpu bounds tasks
 /// ```
+/// # use minipool::MiniPool;
 /// let pool = MiniPool::new();
 /// ```
 pub struct MiniPool {
-    // All senders for send function to a execute
-    senders: Vec<std::sync::mpsc::Sender<FnThread>>,
-    // All threads
-    threads: Vec<std::thread::JoinHandle<()>>,
+    // How submitted jobs reach the workers
+    dispatch: Dispatch,
+    // All threads, shared with workers so a respawn can write its handle back
+    threads: SharedThreads,
+    // Queued/active/panic counters, shared with every worker
+    counters: Arc<Counters>,
     // Balancer
     balance: Box<dyn Balancer>,
     // No Sync and Send
     phantom: PhantomData<*const ()>
-} 
+}
 
 impl MiniPool {
 
@@ -121,96 +443,275 @@ impl MiniPool {
         MiniPoolBuilder::new()
     }
 
+    /// Number of jobs currently executing.
+    pub fn active_count(&self) -> usize {
+        self.counters.active.load(Ordering::Relaxed)
+    }
+
+    /// Number of jobs submitted but not yet picked up by a worker.
+    pub fn queued_count(&self) -> usize {
+        self.counters.queued.load(Ordering::Relaxed)
+    }
+
+    /// Number of worker threads the pool was built with.
+    pub fn max_count(&self) -> usize {
+        self.threads.lock().unwrap().len()
+    }
+
+    /// Number of task panics caught so far across the pool's lifetime.
+    pub fn panic_count(&self) -> usize {
+        self.counters.panics.load(Ordering::Relaxed)
+    }
+
     ///
     /// Сreates a new thread, but each new task is distributed between threads using a balancer
     /// ```
+    /// # use minipool::MiniPool;
     /// fn main() {
-    ///     let mut pool = Minipool::new();
-    /// 
+    ///     let mut pool = MiniPool::new();
+    ///
     ///     pool.spawn(|| {
     ///         for i in 0..1000 {
-    ///             println!("First: {}", i);               
-    ///         } 
+    ///             println!("First: {}", i);
+    ///         }
     ///     });
-    /// 
+    ///
     ///     pool.spawn(|| {
     ///         for i in 0..1000 {
-    ///             println!("Second: {}", i);  
+    ///             println!("Second: {}", i);
     ///         }
-    ///     })
-    /// 
+    ///     });
+    ///
     ///     pool.join_all();
     /// }
     /// ```
-    /// 
+    ///
     pub fn spawn<F: FnOnce() + 'static + Send>(&self, func: F) {
-        let index = self.balance.index(self);
-        self.senders[index].send(Box::new(func)).unwrap();
+        self.counters.wait_for_room();
+        self.counters.queued.fetch_add(1, Ordering::Relaxed);
+        self.enqueue(func);
     }
 
-    /// 
-    ///Creates a new thread and when the code execution duration is greater than the set value, resets the execution
-    /// 
+    ///
+    /// Like `spawn`, but if `queue_capacity` is set and the queue is already
+    /// full, returns the closure back instead of blocking for room.
+    ///
     /// ```
+    /// # use minipool::MiniPool;
+    /// # use std::time::Duration;
     /// fn main() {
-    ///     let mut pool = Minipool::new();
-    /// 
-    ///     pool.spawn_with_timeout(|| {
-    ///           std::thread::sleep(Duration::from_secs(60));
-    ///           println!("end");
-    ///     }, timeout: Duration::from_secs(3));
-    /// 
-    ///     pool.join_all();
+    ///     let pool = MiniPool::builder()
+    ///                             .queue_capacity(1)
+    ///                             .build()
+    ///                             .unwrap();
+    ///
+    ///     pool.spawn(|| std::thread::sleep(Duration::from_secs(1)));
+    ///
+    ///     if pool.try_spawn(|| println!("squeezed in")).is_err() {
+    ///         println!("queue was full");
+    ///     }
     /// }
     /// ```
-    /// 
-    pub fn spawn_with_timeout<F: FnOnce() + 'static + Send>(&mut self, func: F, timeout: Duration) {
-        self.spawn(move || {
-            let time = Instant::now();
-            let handle = std::thread::spawn(func);
-            loop {
-                if time.elapsed() > timeout { break; }
-                if handle.is_finished() { break; }
+    ///
+    pub fn try_spawn<F: FnOnce() + 'static + Send>(&self, func: F) -> Result<(), SpawnError<F>> {
+        if !self.counters.has_room() {
+            return Err(SpawnError(func));
+        }
+
+        self.counters.queued.fetch_add(1, Ordering::Relaxed);
+        self.enqueue(func);
+        Ok(())
+    }
+
+    fn enqueue<F: FnOnce() + 'static + Send>(&self, func: F) {
+        match &self.dispatch {
+            Dispatch::RoundRobin { senders } => {
+                let index = self.balance.index(self);
+                senders[index].send(Message::Job(Box::new(func))).unwrap();
+            }
+            Dispatch::WorkStealing { queue } => {
+                let (jobs, ready) = &**queue;
+                jobs.lock().unwrap().push_back(Message::Job(Box::new(func)));
+                ready.notify_one();
             }
-        })
+        }
+    }
+
+    ///
+    /// Runs the closure directly on a pool worker and gives back a handle
+    /// whose `join` waits no longer than `timeout` for the result, reporting
+    /// `TimedOut` instead. A task that outruns its deadline keeps occupying
+    /// that worker until it finishes — `join` timing out only changes what
+    /// the caller sees, not whether the task is still running — so this
+    /// still goes through `enqueue`/`Counters` like any other `spawn`, and
+    /// `queue_capacity` backpressure still applies to it.
+    ///
+    /// ```
+    /// # use minipool::MiniPool;
+    /// # use std::time::Duration;
+    /// fn main() {
+    ///     let pool = MiniPool::new();
+    ///
+    ///     let handle = pool.spawn_with_timeout(|| {
+    ///           std::thread::sleep(Duration::from_millis(200));
+    ///           "done"
+    ///     }, Duration::from_millis(20));
+    ///
+    ///     assert!(handle.join().is_err());
+    /// }
+    /// ```
+    ///
+    pub fn spawn_with_timeout<F, T>(&self, func: F, timeout: Duration) -> TimeoutHandle<T>
+    where
+        F: FnOnce() -> T + 'static + Send,
+        T: 'static + Send
+    {
+        let shared = Arc::new(TimeoutShared { slot: Mutex::new(None), ready: Condvar::new() });
+        let worker_shared = shared.clone();
+
+        self.spawn(move || {
+            let result = func();
+            *worker_shared.slot.lock().unwrap() = Some(result);
+            worker_shared.ready.notify_one();
+        });
+
+        TimeoutHandle { shared, timeout }
+    }
+
+    ///
+    /// Like `spawn_with_result`, but the closure is handed a `CancellationToken`
+    /// it can poll to cooperatively stop early once cancellation is requested.
+    ///
+    /// ```
+    /// # use minipool::MiniPool;
+    /// fn main() {
+    ///     let pool = MiniPool::new();
+    ///
+    ///     let (token, handle) = pool.spawn_with_cancel(|token| {
+    ///         while !token.is_cancelled() {
+    ///             // do some work
+    ///         }
+    ///         "stopped"
+    ///     });
+    ///
+    ///     token.cancel();
+    ///     handle.join().unwrap();
+    /// }
+    /// ```
+    ///
+    pub fn spawn_with_cancel<F, T>(&self, func: F) -> (CancellationToken, TaskHandle<T>)
+    where
+        F: FnOnce(CancellationToken) -> T + 'static + Send,
+        T: 'static + Send
+    {
+        let token = CancellationToken::new();
+        let task_token = token.clone();
+
+        let handle = self.spawn_with_result(move || func(task_token));
+
+        (token, handle)
+    }
+
+    ///
+    /// Submits the closure to a pool worker like `spawn`, but its return value can be
+    /// collected afterwards through the returned `TaskHandle` instead of being
+    /// smuggled out through shared state.
+    ///
+    /// ```
+    /// # use minipool::MiniPool;
+    /// fn main() {
+    ///     let pool = MiniPool::new();
+    ///
+    ///     let handle = pool.spawn_with_result(|| {
+    ///         1 + 1
+    ///     });
+    ///
+    ///     assert_eq!(2, handle.join().unwrap());
+    /// }
+    /// ```
+    ///
+    pub fn spawn_with_result<F, T>(&self, func: F) -> TaskHandle<T>
+    where
+        F: FnOnce() -> T + 'static + Send,
+        T: 'static + Send
+    {
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        self.spawn(move || {
+            let result = func();
+            let _ = tx.send(result);
+        });
+
+        TaskHandle { receiver: rx }
     }
 
     ///
-    /// Blocks the main thread until all running threads have completed.
-    /// 
+    /// Blocks the main thread until all queued jobs have finished and every
+    /// worker has shut down. Sends one `Terminate` message per worker so it
+    /// stops taking new jobs only after draining whatever was queued ahead
+    /// of it, then blocks on `JoinHandle::join` instead of polling.
+    ///
+    /// Joins in passes instead of a single pass over the original handles:
+    /// a worker that dies and gets respawned by its `Sentinel` writes the
+    /// replacement's handle back into the shared `threads` vec before the
+    /// dying thread fully exits, i.e. before the original handle's `join`
+    /// can return here — so another pass always picks up the replacement.
+    ///
     /// ```
+    /// # use minipool::MiniPool;
     /// fn main() {
-    ///     let mut pool = Minipool::new();
-    /// 
+    ///     let mut pool = MiniPool::new();
+    ///
     ///     pool.spawn(|| {
-    ///           some_function_1();
+    ///         println!("first job");
     ///     });
-    /// 
+    ///
     ///     pool.spawn(|| {
-    ///         some_function_2();
+    ///         println!("second job");
     ///     });
-    /// 
+    ///
     ///     pool.join_all();
     /// }
     /// ```
-    /// 
+    ///
     pub fn join_all(&mut self) {
 
-        self.senders.clear();
+        match &self.dispatch {
+            Dispatch::RoundRobin { senders } => {
+                for sender in senders {
+                    let _ = sender.send(Message::Terminate);
+                }
+            }
+            Dispatch::WorkStealing { queue } => {
+                let (jobs, ready) = &**queue;
+                let mut jobs = jobs.lock().unwrap();
+                for _ in 0..self.threads.lock().unwrap().len() {
+                    jobs.push_back(Message::Terminate);
+                }
+                ready.notify_all();
+            }
+        }
 
         loop {
-            let mut is_finish = true;
-            for i in 0..self.threads.len() {
-                if !self.threads[i].is_finished() {
-                    is_finish = false;
-                }
+            let pending: Vec<_> = self.threads.lock().unwrap().iter_mut().filter_map(|slot| slot.take()).collect();
+
+            if pending.is_empty() {
+                break;
             }
 
-            if is_finish { break; }
+            for thread in pending {
+                let _ = thread.join();
+            }
         }
     }
 }
 
+impl Drop for MiniPool {
+    fn drop(&mut self) {
+        self.join_all();
+    }
+}
+
 
 #[test]
 fn test() {
@@ -233,4 +734,154 @@ fn test() {
 
     assert_eq!(1, *(m.lock().unwrap()))
 
+}
+
+#[test]
+fn test_spawn_with_result() {
+
+    let pool = MiniPool::new();
+
+    let handle = pool.spawn_with_result(|| 2 + 2);
+
+    assert_eq!(4, handle.join().unwrap());
+}
+
+#[test]
+fn test_spawn_with_timeout_times_out() {
+
+    let pool = MiniPool::new();
+
+    let handle = pool.spawn_with_timeout(|| {
+        std::thread::sleep(Duration::from_millis(200));
+        "done"
+    }, Duration::from_millis(20));
+
+    assert_eq!(Err(TimedOut), handle.join());
+}
+
+#[test]
+fn test_spawn_with_cancel_observes_cancellation() {
+
+    let pool = MiniPool::new();
+
+    let (token, handle) = pool.spawn_with_cancel(|token| {
+        while !token.is_cancelled() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        "stopped"
+    });
+
+    token.cancel();
+
+    assert_eq!("stopped", handle.join().unwrap());
+}
+
+#[test]
+fn test_counters() {
+
+    let mut pool = MiniPoolBuilder::new()
+                                    .count_threads(2)
+                                    .build()
+                                    .unwrap();
+
+    assert_eq!(2, pool.max_count());
+    assert_eq!(0, pool.panic_count());
+
+    pool.spawn(|| panic!("boom"));
+    pool.join_all();
+
+    assert_eq!(1, pool.panic_count());
+    assert_eq!(0, pool.active_count());
+    assert_eq!(0, pool.queued_count());
+}
+
+#[test]
+fn test_panic_handler_is_invoked_with_the_payload() {
+
+    use std::sync::*;
+
+    let caught = Arc::new(Mutex::new(None));
+    let recorded = caught.clone();
+
+    let mut pool = MiniPoolBuilder::new()
+                                    .count_threads(1)
+                                    .panic_handler(move |payload| {
+                                        let message = payload.downcast_ref::<&str>().copied().unwrap_or("<unknown>");
+                                        *recorded.lock().unwrap() = Some(message.to_string());
+                                    })
+                                    .build()
+                                    .unwrap();
+
+    pool.spawn(|| panic!("boom"));
+    pool.join_all();
+
+    assert_eq!(Some("boom".to_string()), *caught.lock().unwrap());
+}
+
+#[test]
+fn test_work_stealing_scheduling() {
+
+    use std::sync::*;
+
+    let mut pool = MiniPoolBuilder::new()
+                                    .count_threads(4)
+                                    .scheduling(Scheduling::WorkStealing)
+                                    .build()
+                                    .unwrap();
+
+    let sum = Arc::new(Mutex::new(0));
+
+    for _ in 0..8 {
+        let sum = sum.clone();
+        pool.spawn(move || { *sum.lock().unwrap() += 1 });
+    }
+
+    pool.join_all();
+
+    assert_eq!(8, *(sum.lock().unwrap()));
+}
+
+#[test]
+fn test_try_spawn_rejects_when_queue_is_full() {
+
+    let pool = MiniPoolBuilder::new()
+                                    .count_threads(1)
+                                    .queue_capacity(1)
+                                    .build()
+                                    .unwrap();
+
+    // Keeps the single worker busy so the next job has to sit queued.
+    pool.spawn(|| std::thread::sleep(Duration::from_millis(200)));
+    // Fills the one queue slot.
+    pool.spawn(|| ());
+
+    let ran = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let flag = ran.clone();
+
+    let rejected = pool.try_spawn(move || { flag.store(true, std::sync::atomic::Ordering::Relaxed); });
+    assert!(rejected.is_err());
+
+    // The rejected closure is handed back untouched, so the caller can still run it.
+    (rejected.unwrap_err().0)();
+    assert!(ran.load(std::sync::atomic::Ordering::Relaxed));
+}
+
+#[test]
+fn test_panicking_task_does_not_stop_the_pool() {
+
+    use std::sync::*;
+
+    let mut pool = MiniPoolBuilder::new()
+                                    .count_threads(1)
+                                    .build()
+                                    .unwrap();
+
+    pool.spawn(|| panic!("boom"));
+
+    let m = Arc::new(Mutex::new(0));
+    let n = m.clone();
+    pool.spawn(move || { *n.lock().unwrap() = 1 });
+    pool.join_all();
+
+    assert_eq!(1, *(m.lock().unwrap()))
 }
\ No newline at end of file